@@ -0,0 +1,777 @@
+//! Core fasta-cleaning library: upper-cases sequence characters, rewraps them to a uniform line width,
+//! and drops anything outside a configured [`Alphabet`], while tracking enough bookkeeping to also emit a
+//! samtools-style `.fai` index. The CLI binary built alongside this crate is a thin wrapper around
+//! [`clean_fasta_file`] that adds file/stdio handling, (de)compression and argument parsing;
+//! [`FastaRecords`] is library-only and not used by the binary.
+
+use std::{
+    io::{Error, Read, Write},
+    sync::Mutex,
+};
+
+use log::{debug, info, LevelFilter};
+use simplelog::{ColorChoice, CombinedLogger, TermLogger, TerminalMode};
+
+static LOGGING_INITIALISED: Mutex<bool> = Mutex::new(false);
+
+pub fn initialise_logging(log_level: LevelFilter) {
+    let mut logging_initialised = LOGGING_INITIALISED.lock().unwrap();
+
+    if !*logging_initialised {
+        CombinedLogger::init(vec![TermLogger::new(
+            log_level,
+            Default::default(),
+            TerminalMode::Mixed,
+            ColorChoice::Auto,
+        )])
+        .unwrap();
+
+        info!("Logging initialised successfully");
+        *logging_initialised = true;
+    }
+}
+
+/// A lookup table of the 256 possible bytes, used to decide in O(1) whether a sequence character is
+/// part of the configured alphabet.
+pub type Alphabet = [bool; 256];
+
+/// Builds the [`Alphabet`] lookup table for `name`, which is either one of the built-in presets
+/// (`dna`, `dna-iupac`, `rna`, `protein`) or a literal set of allowed characters.
+pub fn parse_alphabet(name: &str) -> Alphabet {
+    let characters: &[u8] = match name {
+        "dna" => b"ACGT",
+        "dna-iupac" => b"ACGTRYSWKMBDHVN-",
+        "rna" => b"ACGU",
+        "protein" => b"ACDEFGHIKLMNPQRSTVWY",
+        literal => literal.as_bytes(),
+    };
+
+    let mut alphabet = [false; 256];
+    for &character in characters {
+        alphabet[character.to_ascii_uppercase() as usize] = true;
+    }
+    alphabet
+}
+
+enum FastaState {
+    Init,
+    RecordHeader {
+        width: Option<usize>,
+    },
+    RecordHeaderLineBreak {
+        width: Option<usize>,
+    },
+    RecordSequenceWithoutWidth {
+        current_width: usize,
+        current_output_row_width: usize,
+    },
+    RecordSequenceLineBreak {
+        width: usize,
+        current_output_row_width: usize,
+    },
+    RecordSequence {
+        width: usize,
+        current_output_row_width: usize,
+    },
+}
+
+/// The size of the chunks read from the input and flushed to the output, in bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Pulls fixed-size chunks from a reader into a reusable buffer, exposing the unconsumed part as a slice.
+///
+/// This amortises the syscall and bookkeeping cost of reading across many bytes, instead of issuing one
+/// `read` per base.
+struct ChunkedInput<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    position: usize,
+    filled: usize,
+}
+
+impl<R: Read> ChunkedInput<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; CHUNK_SIZE],
+            position: 0,
+            filled: 0,
+        }
+    }
+
+    /// Returns the unconsumed part of the current chunk, refilling it from the reader if it is empty.
+    ///
+    /// An empty slice means the reader has reached EOF.
+    fn fill(&mut self) -> Result<&[u8], Error> {
+        if self.position >= self.filled {
+            self.filled = self.reader.read(&mut self.buffer)?;
+            self.position = 0;
+        }
+        Ok(&self.buffer[self.position..self.filled])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+
+    /// Reads and consumes a single byte, or `None` at EOF.
+    fn next_byte(&mut self) -> Result<Option<u8>, Error> {
+        let chunk = self.fill()?;
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        let byte = chunk[0];
+        self.consume(1);
+        Ok(Some(byte))
+    }
+}
+
+/// Accumulates output bytes in a reusable buffer and flushes them to the writer in one `write_all` call
+/// per chunk, instead of one `write_all` per byte.
+struct ChunkedOutput<W> {
+    writer: W,
+    buffer: Vec<u8>,
+    /// The total number of bytes handed to [`Self::write_byte`] so far, used to compute `.fai` offsets.
+    offset: usize,
+}
+
+impl<W: Write> ChunkedOutput<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            offset: 0,
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.buffer.push(byte);
+        self.offset += 1;
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if !self.buffer.is_empty() {
+            self.writer.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+/// The error returned by [`clean_fasta_file`] and by [`FastaRecords`].
+#[derive(Debug)]
+pub enum CleanError {
+    /// Reading from the input or writing to the output failed.
+    Io(Error),
+    /// A non-whitespace character was found before the first `>` of the file.
+    NonWhitespaceBeforeFirstRecord,
+    /// A `>` was found in the middle of a sequence instead of at the start of a line.
+    UnexpectedRecordStart,
+}
+
+impl std::fmt::Display for CleanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CleanError::Io(error) => write!(f, "I/O error: {error}"),
+            CleanError::NonWhitespaceBeforeFirstRecord => {
+                write!(f, "Found non-whitespace character before first fasta record.")
+            }
+            CleanError::UnexpectedRecordStart => write!(f, "Encountered '>' within sequence."),
+        }
+    }
+}
+
+impl std::error::Error for CleanError {}
+
+impl From<Error> for CleanError {
+    fn from(error: Error) -> Self {
+        CleanError::Io(error)
+    }
+}
+
+/// Statistics about a single [`clean_fasta_file`] run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CleanStats {
+    /// The number of fasta records (`>` headers) encountered.
+    pub records: usize,
+    /// The number of sequence characters read, before alphabet filtering.
+    pub input_bases: usize,
+    /// The number of sequence characters retained in the output, after alphabet filtering.
+    pub retained_bases: usize,
+    /// A samtools-style `.fai` index entry per record, in file order.
+    pub index: Vec<FaiRecord>,
+}
+
+/// A single `.fai` index entry: the name, length, sequence-start offset and line layout of one record in
+/// the cleaned output, in the same five columns samtools writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaiRecord {
+    /// The first whitespace-delimited token of the record's header.
+    pub name: String,
+    /// The total number of sequence bases retained in the output.
+    pub length: usize,
+    /// The byte offset of the first sequence base in the output.
+    pub offset: usize,
+    /// The number of bases on each full output line.
+    pub line_bases: usize,
+    /// The number of bytes (bases plus the line break) of each full output line.
+    pub line_bytes: usize,
+}
+
+/// A single fasta record, as yielded by [`FastaRecords`].
+///
+/// The sequence is the raw, concatenated bytes between this record's header and the next one (or EOF),
+/// with line breaks removed but otherwise unmodified, i.e. neither upper-cased nor alphabet-filtered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The header line, without the leading `>` or the trailing line break.
+    pub header: String,
+    /// The record's sequence bytes, with line breaks removed.
+    pub sequence: Vec<u8>,
+}
+
+/// A lazy, streaming reader of [`Record`]s from a fasta file.
+///
+/// This mirrors the `Records` iterators of established fasta crates: records are parsed one at a time as
+/// the iterator is driven, without loading the whole file into memory, and callers can inspect, count or
+/// filter records before deciding what to do with them.
+pub struct FastaRecords<R> {
+    input: ChunkedInput<R>,
+    /// Whether the input is already positioned right after the `>` that starts the next header.
+    at_header: bool,
+    finished: bool,
+}
+
+impl<R: Read> FastaRecords<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input: ChunkedInput::new(input),
+            at_header: false,
+            finished: false,
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<Record>, CleanError> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        if !self.at_header {
+            loop {
+                match self.input.next_byte()? {
+                    None => {
+                        self.finished = true;
+                        return Ok(None);
+                    }
+                    Some(b'>') => break,
+                    Some(character) => {
+                        if !character.is_ascii_whitespace() {
+                            return Err(CleanError::NonWhitespaceBeforeFirstRecord);
+                        }
+                    }
+                }
+            }
+        }
+        self.at_header = false;
+
+        let mut header = Vec::new();
+        loop {
+            match self.input.next_byte()? {
+                None | Some(b'\n' | b'\r') => break,
+                Some(character) => header.push(character),
+            }
+        }
+
+        let mut sequence = Vec::new();
+        loop {
+            match self.input.next_byte()? {
+                None => {
+                    self.finished = true;
+                    break;
+                }
+                Some(b'\n' | b'\r') => { /* Ignore line breaks within the sequence */ }
+                Some(b'>') => {
+                    self.at_header = true;
+                    break;
+                }
+                Some(character) => sequence.push(character),
+            }
+        }
+
+        Ok(Some(Record {
+            header: String::from_utf8_lossy(&header).into_owned(),
+            sequence,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for FastaRecords<R> {
+    type Item = Result<Record, CleanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_record() {
+            Ok(record) => record.map(Ok),
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// The mutable state threaded through [`step`] and [`handle_eof`] as the cleaning state machine runs:
+/// the output sink, the configured alphabet, the running statistics, and the bookkeeping needed to build
+/// each record's `.fai` entry as its sequence is written.
+struct Context<'a, W> {
+    output: ChunkedOutput<W>,
+    alphabet: &'a Alphabet,
+    stats: CleanStats,
+    /// The bytes of the header of the record currently open (or just finished), sans `>` and line break.
+    header: Vec<u8>,
+    /// The number of bases retained so far in the record currently open.
+    current_length: usize,
+    /// The output offset of the first sequence base of the record currently open.
+    current_offset: usize,
+}
+
+impl<'a, W: Write> Context<'a, W> {
+    fn new(output: W, alphabet: &'a Alphabet) -> Self {
+        Self {
+            output: ChunkedOutput::new(output),
+            alphabet,
+            stats: CleanStats::default(),
+            header: Vec::new(),
+            current_length: 0,
+            current_offset: 0,
+        }
+    }
+
+    /// Pushes the `.fai` entry for the record currently open onto `stats.index`, using `line_bases` as
+    /// its uniform bases-per-line (and `line_bases + 1` as the corresponding bytes-per-line, or 0 when
+    /// `line_bases` is 0).
+    fn finish_record(&mut self, line_bases: usize) {
+        let line_bytes = if line_bases == 0 { 0 } else { line_bases + 1 };
+        self.stats.index.push(FaiRecord {
+            name: header_name(&self.header),
+            length: self.current_length,
+            offset: self.current_offset,
+            line_bases,
+            line_bytes,
+        });
+    }
+
+    /// Starts bookkeeping a new record, to be finished later by [`Self::finish_record`].
+    fn start_record(&mut self) {
+        self.stats.records += 1;
+        self.header.clear();
+        self.current_length = 0;
+    }
+}
+
+/// Returns the first whitespace-delimited token of `header`, i.e. the record name as used by `.fai` files.
+fn header_name(header: &[u8]) -> String {
+    let name_length = header
+        .iter()
+        .position(u8::is_ascii_whitespace)
+        .unwrap_or(header.len());
+    String::from_utf8_lossy(&header[..name_length]).into_owned()
+}
+
+pub fn clean_fasta_file(
+    input: impl Read,
+    output: impl Write,
+    alphabet: &Alphabet,
+) -> Result<CleanStats, CleanError> {
+    let mut input = ChunkedInput::new(input);
+    let mut context = Context::new(output, alphabet);
+    let mut state = FastaState::Init;
+
+    loop {
+        let chunk = input.fill()?;
+        if chunk.is_empty() {
+            handle_eof(state, &mut context)?;
+            break;
+        }
+
+        for &character in chunk {
+            state = step(state, character, &mut context)?;
+        }
+        let consumed = chunk.len();
+        input.consume(consumed);
+    }
+
+    context.output.flush()?;
+    Ok(context.stats)
+}
+
+/// Processes a single character against `state`, writing output and updating `context` as required, and
+/// returns the next state.
+fn step(state: FastaState, character: u8, context: &mut Context<impl Write>) -> Result<FastaState, CleanError> {
+    Ok(match state {
+        FastaState::Init => match character {
+            b'>' => {
+                context.start_record();
+                context.output.write_byte(b'>')?;
+                FastaState::RecordHeader { width: None }
+            }
+            character => {
+                if !character.is_ascii_whitespace() {
+                    return Err(CleanError::NonWhitespaceBeforeFirstRecord);
+                }
+                FastaState::Init
+            }
+        },
+        FastaState::RecordHeader { width } => match character {
+            b'\n' | b'\r' => {
+                context.output.write_byte(b'\n')?;
+                context.current_offset = context.output.offset;
+                FastaState::RecordHeaderLineBreak { width }
+            }
+            character => {
+                context.header.push(character);
+                context.output.write_byte(character)?;
+                FastaState::RecordHeader { width }
+            }
+        },
+        FastaState::RecordHeaderLineBreak { width } => match character {
+            b'\n' | b'\r' => FastaState::RecordHeaderLineBreak { width } /* Ignore further line breaks */,
+            b'>' => {
+                context.finish_record(0);
+                context.start_record();
+                context.output.write_byte(b'>')?;
+                FastaState::RecordHeader { width }
+            }
+            character => {
+                let character = character.to_ascii_uppercase();
+                context.stats.input_bases += 1;
+                let mut current_output_row_width = 0;
+                if context.alphabet[character as usize] {
+                    context.stats.retained_bases += 1;
+                    context.current_length += 1;
+                    current_output_row_width += 1;
+                    context.output.write_byte(character)?;
+                }
+                if let Some(width) = width {
+                    FastaState::RecordSequence {
+                        width,
+                        current_output_row_width,
+                    }
+                } else {
+                    FastaState::RecordSequenceWithoutWidth {
+                        current_width: 1,
+                        current_output_row_width,
+                    }
+                }
+            }
+        },
+        FastaState::RecordSequenceWithoutWidth {
+            mut current_width,
+            mut current_output_row_width,
+        } => match character {
+            b'\n' | b'\r' => {
+                debug_assert!(current_width > 0);
+                if current_output_row_width == current_width {
+                    context.output.write_byte(b'\n')?;
+                    current_output_row_width = 0;
+                }
+                debug!("Found fasta line width {current_width}");
+                FastaState::RecordSequenceLineBreak {
+                    width: current_width,
+                    current_output_row_width,
+                }
+            }
+            b'>' => return Err(CleanError::UnexpectedRecordStart),
+            character => {
+                let character = character.to_ascii_uppercase();
+                current_width += 1;
+                context.stats.input_bases += 1;
+
+                if context.alphabet[character as usize] {
+                    context.stats.retained_bases += 1;
+                    context.current_length += 1;
+                    current_output_row_width += 1;
+                    context.output.write_byte(character)?;
+                }
+
+                FastaState::RecordSequenceWithoutWidth {
+                    current_width,
+                    current_output_row_width,
+                }
+            }
+        },
+        FastaState::RecordSequenceLineBreak {
+            width,
+            mut current_output_row_width,
+        } => match character {
+            b'\n' | b'\r' => FastaState::RecordSequenceLineBreak {
+                width,
+                current_output_row_width,
+            } /* Ignore further line breaks */,
+            b'>' => {
+                context.finish_record(width);
+                context.start_record();
+                debug_assert!(current_output_row_width <= width);
+                if current_output_row_width > 0 {
+                    context.output.write_byte(b'\n')?;
+                }
+                context.output.write_byte(b'>')?;
+                FastaState::RecordHeader { width: Some(width) }
+            }
+            character => {
+                let character = character.to_ascii_uppercase();
+                context.stats.input_bases += 1;
+
+                debug_assert!(current_output_row_width <= width);
+                if current_output_row_width == width {
+                    context.output.write_byte(b'\n')?;
+                    current_output_row_width = 0;
+                }
+
+                if context.alphabet[character as usize] {
+                    context.stats.retained_bases += 1;
+                    context.current_length += 1;
+                    current_output_row_width += 1;
+                    context.output.write_byte(character)?;
+                }
+
+                FastaState::RecordSequence {
+                    width,
+                    current_output_row_width,
+                }
+            }
+        },
+        FastaState::RecordSequence {
+            width,
+            mut current_output_row_width,
+        } => match character {
+            b'\n' | b'\r' => {
+                debug_assert!(current_output_row_width <= width);
+                if current_output_row_width == width {
+                    context.output.write_byte(b'\n')?;
+                    current_output_row_width = 0;
+                }
+                FastaState::RecordSequenceLineBreak {
+                    width,
+                    current_output_row_width,
+                }
+            }
+            b'>' => return Err(CleanError::UnexpectedRecordStart),
+            character => {
+                let character = character.to_ascii_uppercase();
+                context.stats.input_bases += 1;
+
+                debug_assert!(current_output_row_width <= width);
+                if current_output_row_width == width {
+                    context.output.write_byte(b'\n')?;
+                    current_output_row_width = 0;
+                }
+
+                if context.alphabet[character as usize] {
+                    context.stats.retained_bases += 1;
+                    context.current_length += 1;
+                    current_output_row_width += 1;
+                    context.output.write_byte(character)?;
+                }
+
+                FastaState::RecordSequence {
+                    width,
+                    current_output_row_width,
+                }
+            }
+        },
+    })
+}
+
+/// Handles reaching EOF from `state`: writes the line break ending the last sequence row, unless `step`
+/// already wrote it after a full row, and pushes the final record's `.fai` entry, if any.
+fn handle_eof(state: FastaState, context: &mut Context<impl Write>) -> Result<(), CleanError> {
+    match state {
+        FastaState::Init => { /* No record was ever started. */ }
+        FastaState::RecordHeader { .. } => { /* Truncated mid-header; nothing to index. */ }
+        FastaState::RecordHeaderLineBreak { .. } => context.finish_record(0),
+        FastaState::RecordSequenceWithoutWidth { .. } => {
+            context.output.write_byte(b'\n')?;
+            context.finish_record(context.current_length);
+        }
+        FastaState::RecordSequenceLineBreak {
+            width,
+            current_output_row_width,
+        }
+        | FastaState::RecordSequence {
+            width,
+            current_output_row_width,
+        } => {
+            // If the last row already filled the established width exactly, `step` already wrote its
+            // terminating `\n` and reset `current_output_row_width` to 0; writing another one here would
+            // leave a spurious blank line before EOF.
+            if current_output_row_width > 0 {
+                context.output.write_byte(b'\n')?;
+            }
+            context.finish_record(width);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use log::{debug, LevelFilter};
+
+    use crate::{clean_fasta_file, initialise_logging, parse_alphabet, FaiRecord, FastaRecords};
+
+    #[test]
+    fn bench_large_input() {
+        initialise_logging(LevelFilter::Debug);
+
+        let mut input = Vec::with_capacity(8 * 1024 * 1024);
+        input.extend_from_slice(b">large record\n");
+        for i in 0..4_000_000usize {
+            input.push(match i % 8 {
+                0 => b'a',
+                1 => b'c',
+                2 => b'g',
+                3 => b't',
+                4 => b'n', // filtered out, like every non-ACGT character
+                5 => b'\n',
+                6 => b'A',
+                _ => b'T',
+            });
+        }
+
+        let mut output = Vec::new();
+        let start = Instant::now();
+        clean_fasta_file(input.as_slice(), &mut output, &parse_alphabet("dna")).unwrap();
+        let elapsed = start.elapsed();
+        debug!(
+            "Chunked cleaning turned {} input bytes into {} output bytes in {elapsed:?}",
+            input.len(),
+            output.len()
+        );
+
+        assert!(output.starts_with(b">large record\n"));
+        assert!(output.len() < input.len());
+    }
+
+    #[test]
+    fn test() {
+        initialise_logging(LevelFilter::Debug);
+        test_file(
+            b"\r>WGCaC\n\nAACCcxXAA\naacc\n.ef34\nCGG\ntgtcgcgtagcgtgatcgtgtagtcgtag\r.\r>f\nTTT",
+            b">WGCaC\nAACCCAAAA\nCCCGGTGTC\nGCGTAGCGT\nGATCGTGTA\nGTCGTAG\n>f\nTTT\n",
+        );
+    }
+
+    #[test]
+    fn full_width_last_row_does_not_duplicate_trailing_newline() {
+        initialise_logging(LevelFilter::Debug);
+        // The sole row exactly fills the width established from it, so `step` already writes its
+        // terminating '\n' before EOF; handle_eof must not write a second one.
+        test_file(b">r\nACGT\n", b">r\nACGT\n");
+    }
+
+    #[test]
+    fn parse_alphabet_dna_iupac_retains_ambiguity_codes_and_gaps() {
+        initialise_logging(LevelFilter::Debug);
+        test_file_with_alphabet(
+            "dna-iupac",
+            b">r\nacgtryswkmbdhvn-\n",
+            b">r\nACGTRYSWKMBDHVN-\n",
+        );
+    }
+
+    #[test]
+    fn parse_alphabet_rna_retains_u_and_rejects_t() {
+        initialise_logging(LevelFilter::Debug);
+        test_file_with_alphabet("rna", b">r\nacgut\n", b">r\nACGU\n");
+    }
+
+    #[test]
+    fn parse_alphabet_protein_retains_residues_and_rejects_non_standard_codes() {
+        initialise_logging(LevelFilter::Debug);
+        // Z is not one of the 20 standard amino acid codes and is dropped, like X or B would be.
+        test_file_with_alphabet("protein", b">r\nmkveflpqrstz\n", b">r\nMKVEFLPQRST\n");
+    }
+
+    #[test]
+    fn parse_alphabet_literal_character_set() {
+        initialise_logging(LevelFilter::Debug);
+        test_file_with_alphabet("ACGTN-", b">r\nacgtn-x\n", b">r\nACGTN-\n");
+    }
+
+    #[test]
+    fn fasta_records() {
+        initialise_logging(LevelFilter::Debug);
+
+        let records: Vec<_> = FastaRecords::new(b"\n>one\nAACC\ncc\n>two\nXN\n>three\n\n>four\nTT".as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.header.as_str(), record.sequence.as_slice()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("one", b"AACCcc".as_slice()),
+                ("two", b"XN".as_slice()),
+                ("three", b"".as_slice()),
+                ("four", b"TT".as_slice()),
+            ]
+        );
+    }
+
+    #[test]
+    fn index() {
+        initialise_logging(LevelFilter::Debug);
+
+        let input: &[u8] = b"\r>WGCaC\n\nAACCcxXAA\naacc\n.ef34\nCGG\ntgtcgcgtagcgtgatcgtgtagtcgtag\r.\r>f\nTTT";
+        let mut output = Vec::new();
+        let stats = clean_fasta_file(input, &mut output, &parse_alphabet("dna")).unwrap();
+
+        assert_eq!(
+            stats.index,
+            vec![
+                FaiRecord {
+                    name: "WGCaC".to_owned(),
+                    length: 43,
+                    offset: 7,
+                    line_bases: 9,
+                    line_bytes: 10,
+                },
+                FaiRecord {
+                    name: "f".to_owned(),
+                    length: 3,
+                    offset: 58,
+                    // The wrap width detected for the first record carries over to subsequent records
+                    // that never reach it, matching clean_fasta_file's existing line-wrapping behaviour.
+                    line_bases: 9,
+                    line_bytes: 10,
+                },
+            ]
+        );
+    }
+
+    fn test_file(input: &[u8], expected_output: &[u8]) {
+        test_file_with_alphabet("dna", input, expected_output);
+    }
+
+    fn test_file_with_alphabet(alphabet: &str, input: &[u8], expected_output: &[u8]) {
+        debug!("input:\n{}", String::from_utf8_lossy(input));
+        let mut output = Vec::new();
+
+        clean_fasta_file(input, &mut output, &parse_alphabet(alphabet)).unwrap();
+        assert_eq!(
+            output,
+            expected_output,
+            "actual:\n{}\nexpected:\n{}",
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(expected_output),
+        );
+    }
+}